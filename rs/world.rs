@@ -1,9 +1,19 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use crate::network::ClientConnection;
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 use log::{debug, info};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// Side length, in chunks, of a square region file.
+const REGION_SIZE: u32 = 32;
+
 #[derive(Debug, Clone)]
 pub enum WorldError {
     MismatchedChunkSize,
@@ -11,6 +21,7 @@ pub enum WorldError {
     PlaceOutOfLoadedChunk,
     ChunkAlreadyLoaded,
     ChunkAlreadyUnloaded,
+    Io(String),
 }
 
 impl std::fmt::Display for WorldError {
@@ -21,10 +32,17 @@ impl std::fmt::Display for WorldError {
             WorldError::ChunkAlreadyLoaded => write!(f, "chunk already loaded"),
             WorldError::ChunkAlreadyUnloaded => write!(f, "chunk already loaded"),
             WorldError::MismatchedChunkSize => write!(f, "Mismatched chunk size, both width and height must be a multiple of chunk_size"),
+            WorldError::Io(msg) => write!(f, "world io error: {}", msg),
         }
     }
 }
 
+impl From<std::io::Error> for WorldError {
+    fn from(err: std::io::Error) -> Self {
+        WorldError::Io(err.to_string())
+    }
+}
+
 #[derive(Debug)]
 pub struct World {
     pub width: u32,
@@ -35,6 +53,11 @@ pub struct World {
     height_chunks: u32,
     pub players: Vec<ClientConnection>,
     player_loaded: Vec<Vec<u32>>,
+    block_updates: VecDeque<(u32, u32)>,
+    light_updates: VecDeque<(u32, u32)>,
+    /// Indices into `chunks` touched since the last `save`, so only dirty
+    /// regions get rewritten.
+    modified_chunks: HashSet<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -42,7 +65,29 @@ pub struct Chunk {
     pub size: u32,
     pub chunk_x: u32,
     pub chunk_y: u32,
-    pub blocks: Vec<Block>,
+    /// Distinct block types present in this chunk. Index 0 is the fill used
+    /// while the palette has a single entry and the packed array is empty.
+    palette: Vec<Block>,
+    /// Packed palette indices, `bits_per_entry` bits each, laid out tightly
+    /// inside each `u64` without spanning word boundaries. Empty while the
+    /// palette holds a single block (the whole chunk is that block).
+    data: Vec<u64>,
+    /// `ceil(log2(palette.len()))`, or `0` for a single-entry palette.
+    bits_per_entry: u32,
+    /// Per-cell light level (0–15), indexed like the block array.
+    light: Vec<u8>,
+    /// Chunk-local block changes accumulated since the last drain, in chunk
+    /// coordinates. Not persisted — purely a per-tick broadcast buffer.
+    #[serde(skip)]
+    changes: Vec<(u32, u32, Block)>,
+}
+
+/// A chunk's pending changes to broadcast: either an explicit multi-block
+/// batch or a flag to resend the whole chunk when there were too many.
+#[derive(Debug)]
+pub enum ChunkDelta {
+    Blocks(Vec<(u32, u32, Block)>),
+    Whole,
 }
 
 macro_rules! define_blocks {
@@ -95,10 +140,62 @@ impl World {
                 height_chunks,
                 players: vec![],
                 player_loaded,
+                block_updates: VecDeque::new(),
+                light_updates: VecDeque::new(),
+                modified_chunks: HashSet::new(),
             })
         }
     }
     
+    pub fn generate_noise(
+        width: u32,
+        height: u32,
+        chunk_size: u32,
+        seed: u32,
+        sea_level: u32,
+    ) -> Result<World, WorldError> {
+        let mut world = World::generate_empty(width, height, chunk_size)?;
+        if height == 0 {
+            return Ok(world);
+        }
+
+        let start = Instant::now();
+        let base_height = height as f64 / 4.0;
+
+        // Sample the surface of every column in parallel; the fill below is
+        // sequential because `set_block` needs `&mut self`.
+        let surfaces: Vec<u32> = (0..width)
+            .into_par_iter()
+            .map(|x| {
+                let surface = column_surface(x, seed, base_height).round() as i64;
+                surface.clamp(0, height as i64 - 1) as u32
+            })
+            .collect();
+
+        // A thin soil band sits between the stone bedrock and the grass top.
+        const SOIL_DEPTH: u32 = 3;
+        let sea_level = sea_level.min(height - 1);
+        for x in 0..width {
+            let surface = surfaces[x as usize];
+            let soil_start = surface.saturating_sub(SOIL_DEPTH);
+            for y in 0..soil_start {
+                world.set_block(x, y, Block::Stone)?;
+            }
+            for y in soil_start..surface {
+                world.set_block(x, y, Block::Dirt)?;
+            }
+            world.set_block(x, surface, Block::Grass)?;
+            if surface < sea_level {
+                for y in (surface + 1)..=sea_level {
+                    world.set_block(x, y, Block::Water)?;
+                }
+            }
+        }
+
+        info!("Generated noise terrain for {} columns in {:?}", width, start.elapsed());
+        Ok(world)
+    }
+
     pub fn generate_flat(width: u32, height: u32, chunk_size: u32, grass_level: u32) -> Result<World, WorldError> {
         let mut empty_world = World::generate_empty(width, height, chunk_size)?;
 
@@ -136,12 +233,12 @@ impl World {
 
     pub fn get_chunk_mut(&mut self, chunk_x: u32, chunk_y: u32) -> Result<&mut Chunk, WorldError> {
         self.check_out_of_bounds_chunk(chunk_x, chunk_y)?;
-        Ok(&mut self.chunks[(chunk_y * self.height_chunks + chunk_x) as usize])
+        Ok(&mut self.chunks[(chunk_y * self.width_chunks + chunk_x) as usize])
     }
 
     pub fn get_chunk(&self, chunk_x: u32, chunk_y: u32) -> Result<&Chunk, WorldError> {
         self.check_out_of_bounds_chunk(chunk_x, chunk_y)?;
-        Ok(&self.chunks[(chunk_y * self.height_chunks + chunk_x) as usize])
+        Ok(&self.chunks[(chunk_y * self.width_chunks + chunk_x) as usize])
     }
 
     pub fn mark_chunk_loaded_by_id(
@@ -152,7 +249,7 @@ impl World {
     ) -> Result<&Chunk, WorldError> {
         self.check_out_of_bounds_chunk(chunk_x, chunk_y)?;
         let players_loading_chunk =
-            &mut self.player_loaded[(chunk_y * self.height_chunks + chunk_x) as usize];
+            &mut self.player_loaded[(chunk_y * self.width_chunks + chunk_x) as usize];
         match players_loading_chunk
             .iter()
             .any(|&loading| loading == player_loading_id)
@@ -179,7 +276,7 @@ impl World {
     ) -> Result<(), WorldError> {
         self.check_out_of_bounds_chunk(chunk_x, chunk_y)?;
         let players_loading_chunk =
-            &mut self.player_loaded[(chunk_y * self.height_chunks + chunk_x) as usize];
+            &mut self.player_loaded[(chunk_y * self.width_chunks + chunk_x) as usize];
         players_loading_chunk.retain(|&con| player_loading_id != con);
         Ok(())
     }
@@ -197,7 +294,7 @@ impl World {
     ) -> Result<Vec<&ClientConnection>, WorldError> {
         self.get_chunk(chunk_x, chunk_y)?; // to perform the oob check
         let players_loading_ids =
-            &self.player_loaded[(chunk_y * self.height_chunks + chunk_x) as usize];
+            &self.player_loaded[(chunk_y * self.width_chunks + chunk_x) as usize];
         let players_loading = players_loading_ids
             .iter()
             .map(|&id| self.players.iter().find(|&conn| conn.id == id).unwrap())
@@ -214,10 +311,182 @@ impl World {
 
         let chunk = self.get_chunk_mut(chunk_x, chunk_y)?;
         debug!("Found chunk at {}, {}", chunk_x, chunk_y);
+        // Nothing to do — and nothing to wake — if the cell already holds this
+        // block; skipping keeps the simulation queue from churning on no-ops.
+        if chunk.get_block(pos_inside_chunk_x, pos_inside_chunk_y) == block {
+            return Ok(());
+        }
         chunk.set_block(pos_inside_chunk_x, pos_inside_chunk_y, block);
+        self.modified_chunks.insert((chunk_y * self.width_chunks + chunk_x) as usize);
+
+        // Wake the changed cell and its four neighbors for the simulation pass.
+        self.enqueue_update(pos_x, pos_y);
+        if pos_x > 0 {
+            self.enqueue_update(pos_x - 1, pos_y);
+        }
+        self.enqueue_update(pos_x + 1, pos_y);
+        if pos_y > 0 {
+            self.enqueue_update(pos_x, pos_y - 1);
+        }
+        self.enqueue_update(pos_x, pos_y + 1);
+
+        self.relight(pos_x, pos_y, block);
         Ok(())
     }
 
+    pub fn get_block(&self, pos_x: u32, pos_y: u32) -> Result<Block, WorldError> {
+        self.check_out_of_bounds_block(pos_x, pos_y)?;
+        let (chunk_x, chunk_y) = self.get_chunk_block_is_in(pos_x, pos_y)?;
+        let pos_inside_chunk_x = pos_x - chunk_x * self.chunk_size;
+        let pos_inside_chunk_y = pos_y - chunk_y * self.chunk_size;
+        let chunk = self.get_chunk(chunk_x, chunk_y)?;
+        Ok(chunk.get_block(pos_inside_chunk_x, pos_inside_chunk_y))
+    }
+
+    fn enqueue_update(&mut self, pos_x: u32, pos_y: u32) {
+        if pos_x < self.width && pos_y < self.height {
+            self.block_updates.push_back((pos_x, pos_y));
+        }
+    }
+
+    /// Current light level (0–15) at a world cell.
+    pub fn get_light(&self, pos_x: u32, pos_y: u32) -> Result<u8, WorldError> {
+        self.check_out_of_bounds_block(pos_x, pos_y)?;
+        let (chunk_x, chunk_y) = self.get_chunk_block_is_in(pos_x, pos_y)?;
+        let chunk = self.get_chunk(chunk_x, chunk_y)?;
+        Ok(chunk.get_light(pos_x - chunk_x * self.chunk_size, pos_y - chunk_y * self.chunk_size))
+    }
+
+    fn set_light(&mut self, pos_x: u32, pos_y: u32, level: u8) {
+        if let Ok((chunk_x, chunk_y)) = self.get_chunk_block_is_in(pos_x, pos_y) {
+            let (lx, ly) = (pos_x - chunk_x * self.chunk_size, pos_y - chunk_y * self.chunk_size);
+            let idx = (chunk_y * self.width_chunks + chunk_x) as usize;
+            if let Ok(chunk) = self.get_chunk_mut(chunk_x, chunk_y) {
+                chunk.set_light(lx, ly, level);
+            }
+            // Light lives in the chunk body, so a light change dirties the
+            // chunk for persistence just like a block change does.
+            self.modified_chunks.insert(idx);
+        }
+    }
+
+    /// In-bounds four-neighbourhood of a world cell.
+    fn neighbors(&self, pos_x: u32, pos_y: u32) -> Vec<(u32, u32)> {
+        let mut out = Vec::with_capacity(4);
+        if pos_x > 0 {
+            out.push((pos_x - 1, pos_y));
+        }
+        if pos_x + 1 < self.width {
+            out.push((pos_x + 1, pos_y));
+        }
+        if pos_y > 0 {
+            out.push((pos_x, pos_y - 1));
+        }
+        if pos_y + 1 < self.height {
+            out.push((pos_x, pos_y + 1));
+        }
+        out
+    }
+
+    /// Seed sky light from the top of every column and block light from every
+    /// emissive block, then flood the whole world. Call after bulk generation.
+    pub fn recompute_lighting(&mut self) {
+        for (idx, chunk) in self.chunks.iter_mut().enumerate() {
+            chunk.light.iter_mut().for_each(|l| *l = 0);
+            self.modified_chunks.insert(idx);
+        }
+        self.light_updates.clear();
+
+        for x in 0..self.width {
+            for y in (0..self.height).rev() {
+                match self.get_block(x, y) {
+                    Ok(Block::Air) => {
+                        self.set_light(x, y, 15);
+                        self.light_updates.push_back((x, y));
+                    }
+                    _ => break,
+                }
+            }
+        }
+        for x in 0..self.width {
+            for y in 0..self.height {
+                if let Ok(block) = self.get_block(x, y) {
+                    let emission = block.light_emission();
+                    if emission > 0 {
+                        self.set_light(x, y, emission);
+                        self.light_updates.push_back((x, y));
+                    }
+                }
+            }
+        }
+        self.propagate_light(usize::MAX);
+    }
+
+    /// Update lighting incrementally after a single `set_block` change.
+    fn relight(&mut self, pos_x: u32, pos_y: u32, block: Block) {
+        let emission = block.light_emission();
+        if emission > 0 {
+            self.set_light(pos_x, pos_y, emission);
+            self.light_updates.push_back((pos_x, pos_y));
+        } else if block != Block::Air {
+            // An opaque block blocks light: zero this cell and any cells that
+            // were lit by it, then let brighter neighbours re-flood.
+            self.remove_light(pos_x, pos_y);
+        } else {
+            // Air opened up: re-flood from the surrounding cells.
+            self.light_updates.push_back((pos_x, pos_y));
+            for (nx, ny) in self.neighbors(pos_x, pos_y) {
+                self.light_updates.push_back((nx, ny));
+            }
+        }
+    }
+
+    /// Breadth-first removal of light originating at `(pos_x, pos_y)`, queuing
+    /// brighter neighbours for re-propagation.
+    fn remove_light(&mut self, pos_x: u32, pos_y: u32) {
+        let mut queue = VecDeque::new();
+        let level = self.get_light(pos_x, pos_y).unwrap_or(0);
+        self.set_light(pos_x, pos_y, 0);
+        queue.push_back((pos_x, pos_y, level));
+        while let Some((x, y, light)) = queue.pop_front() {
+            for (nx, ny) in self.neighbors(x, y) {
+                let neighbor = self.get_light(nx, ny).unwrap_or(0);
+                if neighbor != 0 && neighbor < light {
+                    self.set_light(nx, ny, 0);
+                    queue.push_back((nx, ny, neighbor));
+                } else if neighbor >= light {
+                    self.light_updates.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
+    /// Flood light outward from queued cells, stopping at opaque blocks, for at
+    /// most `max_updates` cells.
+    fn propagate_light(&mut self, max_updates: usize) {
+        let mut processed = 0;
+        while processed < max_updates {
+            let Some((x, y)) = self.light_updates.pop_front() else {
+                break;
+            };
+            processed += 1;
+
+            let level = self.get_light(x, y).unwrap_or(0);
+            if level <= 1 {
+                continue;
+            }
+            for (nx, ny) in self.neighbors(x, y) {
+                if !matches!(self.get_block(nx, ny), Ok(Block::Air)) {
+                    continue;
+                }
+                if self.get_light(nx, ny).unwrap_or(0) < level - 1 {
+                    self.set_light(nx, ny, level - 1);
+                    self.light_updates.push_back((nx, ny));
+                }
+            }
+        }
+    }
+
     pub fn get_chunk_block_is_in(&self, pos_x: u32, pos_y: u32) -> Result<(u32, u32), WorldError> {
         self.check_out_of_bounds_block(pos_x, pos_y)?;
         let chunk_x = pos_x / self.chunk_size;
@@ -225,9 +494,311 @@ impl World {
         Ok((chunk_x, chunk_y))
     }
     
-    pub fn tick(&mut self) {
-        // todo
-        // tick player collisions, block updates, etc.
+    /// Drain the per-chunk change buffers accumulated by `set_block` and the
+    /// `tick` simulation, yielding one batch per chunk that has a loader. A
+    /// chunk with more than `THRESHOLD` changes is flagged for a whole-chunk
+    /// resend instead of an explicit block list.
+    pub fn drain_chunk_changes(&mut self) -> Vec<(u32, u32, ChunkDelta)> {
+        const THRESHOLD: usize = 64;
+
+        let drained: Vec<(u32, u32, Vec<(u32, u32, Block)>)> = self
+            .chunks
+            .iter_mut()
+            .filter(|chunk| !chunk.changes.is_empty())
+            .map(|chunk| (chunk.chunk_x, chunk.chunk_y, std::mem::take(&mut chunk.changes)))
+            .collect();
+
+        let mut out = Vec::new();
+        for (chunk_x, chunk_y, changes) in drained {
+            match self.get_list_of_players_loading_chunk(chunk_x, chunk_y) {
+                Ok(players) if !players.is_empty() => {
+                    let delta = if changes.len() > THRESHOLD {
+                        ChunkDelta::Whole
+                    } else {
+                        ChunkDelta::Blocks(changes)
+                    };
+                    out.push((chunk_x, chunk_y, delta));
+                }
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// Stream chunks around a moving player: mark chunks within
+    /// `render_distance` (in chunks) of the player's position as loaded and
+    /// unmark ones that fell out of range. Returns `(newly_loaded,
+    /// newly_unloaded)` chunk coordinates for the network layer to act on.
+    pub fn update_loaded_chunks(
+        &mut self,
+        player_id: u32,
+        pos_x: u32,
+        pos_y: u32,
+        render_distance: u32,
+    ) -> (Vec<(u32, u32)>, Vec<(u32, u32)>) {
+        let (center_x, center_y) = (pos_x / self.chunk_size, pos_y / self.chunk_size);
+
+        // Chunks that should be loaded after the move (clamped to the world).
+        let mut desired = Vec::new();
+        let min_x = center_x.saturating_sub(render_distance);
+        let min_y = center_y.saturating_sub(render_distance);
+        let max_x = (center_x + render_distance).min(self.width_chunks.saturating_sub(1));
+        let max_y = (center_y + render_distance).min(self.height_chunks.saturating_sub(1));
+        for cy in min_y..=max_y {
+            for cx in min_x..=max_x {
+                desired.push((cx, cy));
+            }
+        }
+
+        // Chunks currently marked loaded for this player.
+        let current: Vec<(u32, u32)> = self
+            .player_loaded
+            .iter()
+            .enumerate()
+            .filter(|(_, loaders)| loaders.contains(&player_id))
+            .map(|(idx, _)| {
+                let idx = idx as u32;
+                (idx % self.width_chunks, idx / self.width_chunks)
+            })
+            .collect();
+
+        let newly_loaded: Vec<(u32, u32)> = desired
+            .iter()
+            .copied()
+            .filter(|coord| !current.contains(coord))
+            .collect();
+        let newly_unloaded: Vec<(u32, u32)> = current
+            .iter()
+            .copied()
+            .filter(|coord| !desired.contains(coord))
+            .collect();
+
+        for &(cx, cy) in &newly_loaded {
+            let _ = self.mark_chunk_loaded_by_id(cx, cy, player_id);
+        }
+        for &(cx, cy) in &newly_unloaded {
+            let _ = self.unmark_loaded_chunk_for(cx, cy, player_id);
+        }
+
+        (newly_loaded, newly_unloaded)
+    }
+
+    /// Persist the world, rewriting only the region files whose chunks were
+    /// modified since the previous save. Chunks are grouped `REGION_SIZE`
+    /// square per file with a header table of per-chunk offsets and lengths,
+    /// each chunk body zlib-compressed.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<(), WorldError> {
+        let path = path.as_ref();
+        fs::create_dir_all(path)?;
+
+        let mut dirty_regions: HashSet<(u32, u32)> = HashSet::new();
+        for &idx in &self.modified_chunks {
+            let chunk = &self.chunks[idx];
+            dirty_regions.insert((chunk.chunk_x / REGION_SIZE, chunk.chunk_y / REGION_SIZE));
+        }
+
+        let start = Instant::now();
+        for (rx, ry) in &dirty_regions {
+            self.write_region(path, *rx, *ry)?;
+        }
+        self.modified_chunks.clear();
+        info!("Saved {} region(s) in {:?}", dirty_regions.len(), start.elapsed());
+        Ok(())
+    }
+
+    /// Load a world from region files on disk, reading every chunk up front
+    /// through `read_chunk`, which seeks to a single chunk's body. Chunks
+    /// absent from disk stay empty.
+    pub fn load(
+        path: impl AsRef<Path>,
+        width: u32,
+        height: u32,
+        chunk_size: u32,
+    ) -> Result<World, WorldError> {
+        let mut world = World::generate_empty(width, height, chunk_size)?;
+        let path = path.as_ref();
+        for cy in 0..world.height_chunks {
+            for cx in 0..world.width_chunks {
+                if let Some(chunk) = World::read_chunk(path, cx, cy)? {
+                    let idx = (cy * world.width_chunks + cx) as usize;
+                    world.chunks[idx] = chunk;
+                }
+            }
+        }
+        Ok(world)
+    }
+
+    fn region_path(path: &Path, rx: u32, ry: u32) -> PathBuf {
+        path.join(format!("r.{}.{}.region", rx, ry))
+    }
+
+    fn write_region(&self, path: &Path, rx: u32, ry: u32) -> Result<(), WorldError> {
+        let slots = (REGION_SIZE * REGION_SIZE) as usize;
+        let mut offsets = vec![0u32; slots];
+        let mut lengths = vec![0u32; slots];
+        let mut body: Vec<u8> = Vec::new();
+
+        for chunk in &self.chunks {
+            if chunk.chunk_x / REGION_SIZE != rx || chunk.chunk_y / REGION_SIZE != ry {
+                continue;
+            }
+            let local =
+                ((chunk.chunk_y % REGION_SIZE) * REGION_SIZE + (chunk.chunk_x % REGION_SIZE)) as usize;
+            let raw = bincode::serialize(chunk).map_err(|e| WorldError::Io(e.to_string()))?;
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&raw)?;
+            let compressed = encoder.finish()?;
+            offsets[local] = body.len() as u32;
+            lengths[local] = compressed.len() as u32;
+            body.extend_from_slice(&compressed);
+        }
+
+        let mut out = Vec::with_capacity(slots * 8 + body.len());
+        for slot in 0..slots {
+            out.extend_from_slice(&offsets[slot].to_le_bytes());
+            out.extend_from_slice(&lengths[slot].to_le_bytes());
+        }
+        out.extend_from_slice(&body);
+        fs::write(World::region_path(path, rx, ry), out)?;
+        Ok(())
+    }
+
+    /// Read a single chunk from its region file, seeking straight to its body
+    /// via the header table. Returns `None` if the region or chunk is absent.
+    pub fn read_chunk(path: &Path, chunk_x: u32, chunk_y: u32) -> Result<Option<Chunk>, WorldError> {
+        let region = World::region_path(path, chunk_x / REGION_SIZE, chunk_y / REGION_SIZE);
+        if !region.exists() {
+            return Ok(None);
+        }
+
+        let slots = (REGION_SIZE * REGION_SIZE) as usize;
+        let header_len = slots * 8;
+        let local =
+            ((chunk_y % REGION_SIZE) * REGION_SIZE + (chunk_x % REGION_SIZE)) as usize;
+
+        let mut file = fs::File::open(&region)?;
+        let mut header = vec![0u8; header_len];
+        file.read_exact(&mut header)?;
+        let base = local * 8;
+        let offset = u32::from_le_bytes(header[base..base + 4].try_into().unwrap());
+        let length = u32::from_le_bytes(header[base + 4..base + 8].try_into().unwrap());
+        if length == 0 {
+            return Ok(None);
+        }
+
+        file.seek(SeekFrom::Start(header_len as u64 + offset as u64))?;
+        let mut compressed = vec![0u8; length as usize];
+        file.read_exact(&mut compressed)?;
+        let mut decoder = ZlibDecoder::new(&compressed[..]);
+        let mut raw = Vec::new();
+        decoder.read_to_end(&mut raw)?;
+        let chunk: Chunk = bincode::deserialize(&raw).map_err(|e| WorldError::Io(e.to_string()))?;
+        Ok(Some(chunk))
+    }
+
+    /// Run one bounded pass of the block-update simulation: falling blocks
+    /// drop into the air below them and water flows down or spreads sideways.
+    /// Returns the `(x, y, block)` cells that changed so callers can broadcast
+    /// them to players loading the affected chunks.
+    pub fn tick(&mut self) -> Vec<(u32, u32, Block)> {
+        const MAX_UPDATES: usize = 1024;
+
+        let mut changed = Vec::new();
+        let mut processed = 0;
+        while processed < MAX_UPDATES {
+            let Some((x, y)) = self.block_updates.pop_front() else {
+                break;
+            };
+            processed += 1;
+
+            let block = match self.get_block(x, y) {
+                Ok(block) => block,
+                Err(_) => continue,
+            };
+            let below = if y > 0 { self.get_block(x, y - 1).ok() } else { None };
+
+            match block {
+                Block::Water => {
+                    // Water conserves matter by *moving*, and only ever spreads
+                    // toward a cell it could then fall out of. A cell with a
+                    // solid floor and no downhill escape stays put, so a walled
+                    // pocket or an isolated drop settles instead of sloshing
+                    // back and forth forever.
+                    let drains_down = |w: &World, nx: u32| {
+                        w.get_block(nx, y).ok() == Some(Block::Air)
+                            && y > 0
+                            && w.get_block(nx, y - 1).ok() == Some(Block::Air)
+                    };
+                    if below == Some(Block::Air) {
+                        changed.extend(self.move_block(x, y, x, y - 1, Block::Water));
+                    } else if x > 0 && drains_down(self, x - 1) {
+                        changed.extend(self.move_block(x, y, x - 1, y, Block::Water));
+                    } else if drains_down(self, x + 1) {
+                        changed.extend(self.move_block(x, y, x + 1, y, Block::Water));
+                    }
+                }
+                Block::Stone | Block::Log => {
+                    if below == Some(Block::Air) {
+                        changed.extend(self.move_block(x, y, x, y - 1, block));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        const MAX_LIGHT_UPDATES: usize = 1024;
+        self.propagate_light(MAX_LIGHT_UPDATES);
+
+        changed
+    }
+
+    /// Clear `(from_x, from_y)` to air and write `block` at `(to_x, to_y)`,
+    /// returning both resulting cells.
+    fn move_block(
+        &mut self,
+        from_x: u32,
+        from_y: u32,
+        to_x: u32,
+        to_y: u32,
+        block: Block,
+    ) -> Vec<(u32, u32, Block)> {
+        let _ = self.set_block(from_x, from_y, Block::Air);
+        let _ = self.set_block(to_x, to_y, block);
+        vec![(from_x, from_y, Block::Air), (to_x, to_y, block)]
+    }
+}
+
+/// Deterministic 1D hash producing a value in `[0.0, 1.0)` for a lattice
+/// point, used as the per-octave random source for the value noise.
+fn lattice_value(lattice_x: i64, seed: u32, period: u32) -> f64 {
+    let mut h = (lattice_x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h ^= (seed as u64).wrapping_add((period as u64).wrapping_mul(0x85EB_CA6B));
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+    (h >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Sum several octaves of interpolated value noise to get the surface height
+/// for world column `x`, added on top of `base_height`.
+fn column_surface(x: u32, seed: u32, base_height: f64) -> f64 {
+    const OCTAVES: [(u32, f64); 4] = [(128, 32.0), (64, 16.0), (32, 8.0), (16, 4.0)];
+    let mut height = base_height;
+    for (period, amplitude) in OCTAVES {
+        let cell = (x / period) as i64;
+        let t = (x % period) as f64 / period as f64;
+        let low = lattice_value(cell, seed, period);
+        let high = lattice_value(cell + 1, seed, period);
+        height += (low + (high - low) * t) * amplitude;
+    }
+    height
+}
+
+/// Minimum bits needed to index `len` palette entries; `0` for a single entry.
+fn bits_for(len: usize) -> u32 {
+    if len <= 1 {
+        0
+    } else {
+        (usize::BITS - (len - 1).leading_zeros()).max(1)
     }
 }
 
@@ -237,13 +808,88 @@ impl Chunk {
             size,
             chunk_x,
             chunk_y,
-            blocks: (0..size.pow(2)).map(|_| Block::Air).collect(),
+            palette: vec![Block::Air],
+            data: vec![],
+            bits_per_entry: 0,
+            light: vec![0; size.pow(2) as usize],
+            changes: vec![],
         }
     }
 
+    fn get_light(&self, chunk_pos_x: u32, chunk_pos_y: u32) -> u8 {
+        self.light[(chunk_pos_y * self.size + chunk_pos_x) as usize]
+    }
+
+    fn set_light(&mut self, chunk_pos_x: u32, chunk_pos_y: u32, level: u8) {
+        self.light[(chunk_pos_y * self.size + chunk_pos_x) as usize] = level;
+    }
+
+    fn cells(&self) -> usize {
+        self.size.pow(2) as usize
+    }
+
+    /// Read the raw palette index stored for cell `idx`.
+    fn index_at(&self, idx: usize) -> usize {
+        if self.bits_per_entry == 0 {
+            return 0;
+        }
+        let per_word = 64 / self.bits_per_entry as usize;
+        let word = self.data[idx / per_word];
+        let offset = (idx % per_word) * self.bits_per_entry as usize;
+        let mask = (1u64 << self.bits_per_entry) - 1;
+        ((word >> offset) & mask) as usize
+    }
+
+    /// Write the raw palette index for cell `idx` (requires `bits_per_entry > 0`).
+    fn set_index_at(&mut self, idx: usize, value: usize) {
+        let per_word = 64 / self.bits_per_entry as usize;
+        let offset = (idx % per_word) * self.bits_per_entry as usize;
+        let mask = (1u64 << self.bits_per_entry) - 1;
+        let word = &mut self.data[idx / per_word];
+        *word = (*word & !(mask << offset)) | ((value as u64 & mask) << offset);
+    }
+
+    /// Re-pack the index array to `new_bits` bits per entry, preserving the
+    /// block stored in every cell.
+    fn repack(&mut self, new_bits: u32) {
+        let cells = self.cells();
+        let old: Vec<usize> = (0..cells).map(|idx| self.index_at(idx)).collect();
+        self.bits_per_entry = new_bits;
+        if new_bits == 0 {
+            self.data = vec![];
+            return;
+        }
+        let per_word = 64 / new_bits as usize;
+        self.data = vec![0u64; cells.div_ceil(per_word)];
+        for (idx, value) in old.into_iter().enumerate() {
+            self.set_index_at(idx, value);
+        }
+    }
+
+    pub fn get_block(&self, chunk_pos_x: u32, chunk_pos_y: u32) -> Block {
+        let idx = (chunk_pos_y * self.size + chunk_pos_x) as usize;
+        self.palette[self.index_at(idx)]
+    }
+
     fn set_block(&mut self, chunk_pos_x: u32, chunk_pos_y: u32, block: Block) -> &mut Self {
         let idx = (chunk_pos_y * self.size + chunk_pos_x) as usize;
-        self.blocks[idx] = block;
+
+        let palette_idx = match self.palette.iter().position(|&b| b == block) {
+            Some(existing) => existing,
+            None => {
+                self.palette.push(block);
+                let needed = bits_for(self.palette.len());
+                if needed > self.bits_per_entry {
+                    self.repack(needed);
+                }
+                self.palette.len() - 1
+            }
+        };
+
+        if self.bits_per_entry > 0 {
+            self.set_index_at(idx, palette_idx);
+        }
+        self.changes.push((chunk_pos_x, chunk_pos_y, block));
         debug!(
             "[Chunk at ({}, {})] Set block index {} to {:?}",
             self.chunk_x, self.chunk_y, idx, block
@@ -260,4 +906,76 @@ define_blocks! {
     Leaves = 4,
     Water = 5,
     Wood = 6,
+    Glowstone = 7,
+    Dirt = 8,
+}
+
+impl Block {
+    /// Light level this block emits on its own (0 for non-emissive blocks).
+    pub fn light_emission(self) -> u8 {
+        match self {
+            Block::Glowstone => 15,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_round_trip_through_bit_widenings() {
+        // Nine distinct blocks force the palette through 1-, 2-, 3- and
+        // 4-bit entry widths as they are added.
+        let blocks = [
+            Block::Air,
+            Block::Grass,
+            Block::Stone,
+            Block::Log,
+            Block::Leaves,
+            Block::Water,
+            Block::Wood,
+            Block::Glowstone,
+            Block::Dirt,
+        ];
+
+        let size = 16;
+        let mut chunk = Chunk::empty(size, 0, 0);
+        for y in 0..size {
+            for x in 0..size {
+                let block = blocks[((y * size + x) as usize) % blocks.len()];
+                chunk.set_block(x, y, block);
+            }
+        }
+
+        for y in 0..size {
+            for x in 0..size {
+                let expected = blocks[((y * size + x) as usize) % blocks.len()];
+                assert_eq!(chunk.get_block(x, y), expected, "cell ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn save_load_round_trip_preserves_blocks_and_light() {
+        let (width, height, chunk_size) = (64, 64, 16);
+        let mut world = World::generate_flat(width, height, chunk_size, 8).unwrap();
+        world.set_block(10, 20, Block::Glowstone).unwrap();
+        world.recompute_lighting();
+
+        let dir = std::env::temp_dir().join("yourcraft_region_roundtrip");
+        let _ = fs::remove_dir_all(&dir);
+        world.save(&dir).unwrap();
+
+        let loaded = World::load(&dir, width, height, chunk_size).unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                assert_eq!(loaded.get_block(x, y), world.get_block(x, y), "block ({}, {})", x, y);
+                assert_eq!(loaded.get_light(x, y), world.get_light(x, y), "light ({}, {})", x, y);
+            }
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }